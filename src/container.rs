@@ -0,0 +1,218 @@
+use crate::dbg::{Dbg, DenseDbg, HashDbg, SparseDbg};
+use crate::kmer::{Base, IntKmer, IntTag, Kmer};
+use anyhow::{bail, ensure, Result};
+use std::io::{Read, Write};
+use sucds::Serializable;
+
+pub(crate) const MAGIC: [u8; 4] = *b"TDBG";
+pub(crate) const FORMAT_VERSION: u8 = 1;
+// Only consumed directly by `mmap`'s header parser; `save`/`load` below
+// write/skip the same fields without naming their combined length.
+#[cfg_attr(not(feature = "mmap"), allow(dead_code))]
+pub(crate) const HEADER_LEN: usize = MAGIC.len() + 1 + 1 + 8 + 1;
+
+/// On-disk representation tag, written right after the format version so a
+/// loader can tell a `HashDbg` dump from a `DenseDbg`/`SparseDbg` one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Repr {
+    Hash = 0,
+    Dense = 1,
+    Sparse = 2,
+}
+
+impl Repr {
+    const COUNT: u8 = 3;
+    const ALL: [Self; Self::COUNT as usize] = [Self::Hash, Self::Dense, Self::Sparse];
+}
+
+impl TryFrom<u8> for Repr {
+    type Error = anyhow::Error;
+
+    fn try_from(tag: u8) -> Result<Self> {
+        if tag >= Self::COUNT {
+            bail!("unknown representation tag {tag}");
+        }
+        Ok(Self::ALL[tag as usize])
+    }
+}
+
+/// Implemented by every on-disk `Dbg` backend so [`save`]/[`load`] can stamp
+/// and check the representation tag without the caller repeating it.
+pub trait TaggedDbg: Serializable {
+    const REPR: Repr;
+}
+
+impl<const K: usize, T: Base + core::hash::Hash> TaggedDbg for HashDbg<K, T> {
+    const REPR: Repr = Repr::Hash;
+}
+
+impl<const K: usize, T: Base> TaggedDbg for DenseDbg<K, T> {
+    const REPR: Repr = Repr::Dense;
+}
+
+impl<const K: usize, T: Base> TaggedDbg for SparseDbg<K, T> {
+    const REPR: Repr = Repr::Sparse;
+}
+
+/// Writes `dbg` behind a header recording the container format version, the
+/// representation, `K` and the integer width `T`, so [`load`] can refuse to
+/// deserialize into the wrong shape instead of silently producing garbage.
+pub fn save<const K: usize, T: Base, DT>(dbg: &DT, mut writer: impl Write) -> Result<usize>
+where
+    IntKmer<K, T>: Kmer<K, T>,
+    DT: Dbg<K, T, IntKmer<K, T>> + TaggedDbg,
+{
+    let mut n = writer.write(&MAGIC)?;
+    n += writer.write(&[FORMAT_VERSION])?;
+    n += writer.write(&[DT::REPR as u8])?;
+    n += writer.write(&(K as u64).to_le_bytes())?;
+    n += writer.write(&[T::int_tag() as u8])?;
+    n += dbg.serialize_into(&mut writer)?;
+    Ok(n)
+}
+
+/// Reads back a `DT` saved with [`save`], returning a descriptive error on a
+/// mismatched magic, version, representation, `K` or integer width instead
+/// of deserializing into the wrong shape.
+pub fn load<const K: usize, T: Base, DT>(mut reader: impl Read) -> Result<DT>
+where
+    IntKmer<K, T>: Kmer<K, T>,
+    DT: Dbg<K, T, IntKmer<K, T>> + TaggedDbg,
+{
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    ensure!(magic == MAGIC, "not a tinydbg container (bad magic)");
+
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    ensure!(
+        byte[0] == FORMAT_VERSION,
+        "unsupported container format version {} (expected {FORMAT_VERSION})",
+        byte[0]
+    );
+
+    reader.read_exact(&mut byte)?;
+    let repr = Repr::try_from(byte[0])?;
+    ensure!(
+        repr == DT::REPR,
+        "expected a {:?} representation, found {:?}",
+        DT::REPR,
+        repr
+    );
+
+    let mut k_bytes = [0u8; 8];
+    reader.read_exact(&mut k_bytes)?;
+    let stored_k = u64::from_le_bytes(k_bytes);
+    ensure!(stored_k == K as u64, "expected K = {K}, found K = {stored_k}");
+
+    reader.read_exact(&mut byte)?;
+    let tag = IntTag::try_from(byte[0])?;
+    ensure!(
+        tag == T::int_tag(),
+        "expected integer type {:?}, found {:?}",
+        T::int_tag(),
+        tag
+    );
+
+    DT::deserialize_from(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dbg::{DbgBuilder, DenseDbgBuilder, HashDbgBuilder, SparseDbgBuilder};
+    use crate::kmer::IntKmer;
+
+    const K: usize = 4;
+
+    fn sample_kmers() -> [IntKmer<K, u32>; 3] {
+        [
+            IntKmer::from_chars(b"ATCG"),
+            IntKmer::from_chars(b"GGGG"),
+            IntKmer::from_chars(b"TTTT"),
+        ]
+    }
+
+    #[test]
+    fn hash_dbg_save_load_round_trip() {
+        let mut builder = HashDbgBuilder::<K, u32>::new();
+        let kmers = sample_kmers();
+        kmers.iter().for_each(|&kmer| builder.insert(kmer));
+        let dbg = builder.build();
+
+        let mut buf = Vec::new();
+        save::<K, u32, _>(&dbg, &mut buf).unwrap();
+        let loaded: HashDbg<K, u32> = load(buf.as_slice()).unwrap();
+        for kmer in kmers {
+            assert_eq!(dbg.contains(kmer), loaded.contains(kmer));
+            assert!(loaded.contains(kmer));
+        }
+    }
+
+    #[test]
+    fn dense_dbg_save_load_round_trip() {
+        let mut builder = DenseDbgBuilder::<K, u32>::new();
+        let kmers = sample_kmers();
+        kmers.iter().for_each(|&kmer| builder.insert(kmer));
+        let dbg = builder.build();
+
+        let mut buf = Vec::new();
+        save::<K, u32, _>(&dbg, &mut buf).unwrap();
+        let loaded: DenseDbg<K, u32> = load(buf.as_slice()).unwrap();
+        for kmer in kmers {
+            assert!(loaded.contains(kmer));
+        }
+    }
+
+    #[test]
+    fn sparse_dbg_save_load_round_trip() {
+        let mut builder = SparseDbgBuilder::<K, u32>::new();
+        let kmers = sample_kmers();
+        kmers.iter().for_each(|&kmer| builder.insert(kmer));
+        let dbg = builder.build();
+
+        let mut buf = Vec::new();
+        save::<K, u32, _>(&dbg, &mut buf).unwrap();
+        let loaded: SparseDbg<K, u32> = load(buf.as_slice()).unwrap();
+        for kmer in kmers {
+            assert!(loaded.contains(kmer));
+        }
+    }
+
+    #[test]
+    fn load_rejects_representation_mismatch() {
+        let mut builder = HashDbgBuilder::<K, u32>::new();
+        builder.insert(IntKmer::from_chars(b"ATCG"));
+        let dbg = builder.build();
+
+        let mut buf = Vec::new();
+        save::<K, u32, _>(&dbg, &mut buf).unwrap();
+        let err = load::<K, u32, DenseDbg<K, u32>>(buf.as_slice()).err().unwrap();
+        assert!(err.to_string().contains("representation"));
+    }
+
+    #[test]
+    fn load_rejects_k_mismatch() {
+        let mut builder = HashDbgBuilder::<K, u32>::new();
+        builder.insert(IntKmer::from_chars(b"ATCG"));
+        let dbg = builder.build();
+
+        let mut buf = Vec::new();
+        save::<K, u32, _>(&dbg, &mut buf).unwrap();
+        let err = load::<5, u32, HashDbg<5, u32>>(buf.as_slice()).err().unwrap();
+        assert!(err.to_string().contains('K'));
+    }
+
+    #[test]
+    fn load_rejects_int_type_mismatch() {
+        let mut builder = HashDbgBuilder::<K, u32>::new();
+        builder.insert(IntKmer::from_chars(b"ATCG"));
+        let dbg = builder.build();
+
+        let mut buf = Vec::new();
+        save::<K, u32, _>(&dbg, &mut buf).unwrap();
+        let err = load::<K, u64, HashDbg<K, u64>>(buf.as_slice()).err().unwrap();
+        assert!(err.to_string().contains("integer type"));
+    }
+}
@@ -1,19 +1,62 @@
-use crate::kmer::{Base, IntKmer, Kmer};
-use ahash::{HashSet, HashSetExt};
+use crate::kmer::{Base, Kmer};
+#[cfg(any(feature = "alloc", feature = "std"))]
+use crate::kmer::IntKmer;
+#[cfg(feature = "std")]
+use alloc::collections::BTreeSet;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use anyhow::Result;
-use std::collections::BTreeSet;
+#[cfg(feature = "std")]
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use crate::reads::{Fasta, ReadProcess};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
 use std::io::{Read, Write};
-use std::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::slice::Iter;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "std")]
+use std::thread::{self, ThreadId};
+// `sucds`'s bit vectors and Elias-Fano sequences reach for `Vec`/`std::io`
+// internally regardless of its own `std` feature flag, so `DenseDbg` and
+// `SparseDbg` (which embed them) need `std`, not just `alloc`; only
+// `HashDbg` (with `alloc`) and the k-mer encoding in `kmer.rs` build
+// without `std`.
+#[cfg(feature = "std")]
 use sucds::bit_vectors::BitVector;
+#[cfg(feature = "std")]
 use sucds::mii_sequences::{EliasFano, EliasFanoBuilder};
+#[cfg(feature = "std")]
 use sucds::Serializable;
 
+/// A `hashbrown`-backed hash set, so `HashDbg` stays usable without `std`.
+#[cfg(feature = "alloc")]
+type AHashSet<T> = hashbrown::HashSet<T, ahash::RandomState>;
+
+/// `ahash::RandomState::default()` pulls in a runtime RNG that needs `std`,
+/// so `HashDbg` seeds its hasher with fixed constants instead, keeping it
+/// usable under a bare `alloc` build.
+#[cfg(feature = "alloc")]
+fn ahash_state() -> ahash::RandomState {
+    ahash::RandomState::with_seeds(
+        0x243f_6a88_85a3_08d3,
+        0x1319_8a2e_0370_7344,
+        0xa409_3822_299f_31d0,
+        0x082e_fa98_ec4e_6c89,
+    )
+}
+
 pub trait Dbg<const K: usize, T, KT>
 where
     T: Base,
     KT: Kmer<K, T>,
 {
     fn contains(&self, kmer: KT) -> bool;
+    #[cfg(feature = "alloc")]
     fn successors(&self, kmer: KT) -> Vec<KT> {
         kmer.successors()
             .into_iter()
@@ -22,6 +65,7 @@ where
     }
 }
 
+#[cfg(feature = "alloc")]
 pub trait DbgBuilder<const K: usize, T, KT, DT>
 where
     T: Base,
@@ -29,33 +73,68 @@ where
     DT: Dbg<K, T, KT>,
 {
     fn new() -> Self;
-    fn insert(self, kmer: IntKmer<K, T>) -> Self;
+    fn insert(&mut self, kmer: IntKmer<K, T>);
     fn build(self) -> DT;
 }
 
+#[cfg(feature = "alloc")]
 pub struct HashDbg<const K: usize, T: Base> {
-    data: HashSet<T>,
+    data: AHashSet<T>,
 }
 
+#[cfg(feature = "alloc")]
 pub type HashDbgBuilder<const K: usize, T> = HashDbg<K, T>;
 
+#[cfg(feature = "std")]
 pub struct DenseDbg<const K: usize, T: Base> {
     data: BitVector,
     phantom: PhantomData<T>,
 }
 
+#[cfg(feature = "std")]
 pub type DenseDbgBuilder<const K: usize, T> = DenseDbg<K, T>;
 
+#[cfg(feature = "std")]
 pub struct SparseDbg<const K: usize, T: Base> {
     data: EliasFano,
     phantom: PhantomData<T>,
 }
 
+#[cfg(feature = "std")]
 pub struct SparseDbgBuilder<const K: usize, T: Base> {
     positions: BTreeSet<T>,
 }
 
-macro_rules! impl_traits {
+// `sucds`'s `BitVector`/`EliasFano` can't be built without `std` (see the
+// import comment above), so unlike `HashDbg`, these two backends need the
+// `std` feature even for lookup-only queries.
+#[cfg(feature = "std")]
+macro_rules! impl_dbg {
+($($t:ty),+) => {$(
+    impl<const K: usize> Dbg<K, $t, IntKmer<K, $t>> for DenseDbg<K, $t> {
+        fn contains(&self, kmer: IntKmer<K, $t>) -> bool {
+            let pos = kmer.to_int() as usize;
+            self.data.get_bit(pos).expect("Out of bounds")
+        }
+    }
+
+    impl<const K: usize> Dbg<K, $t, IntKmer<K, $t>> for SparseDbg<K, $t> {
+        fn contains(&self, kmer: IntKmer<K, $t>) -> bool {
+            let pos = kmer.to_int() as usize;
+            if let Some(rank) = self.data.rank(pos) {
+                self.data.select(rank) == Some(pos)
+            } else {
+                false
+            }
+        }
+    }
+)*}}
+
+#[cfg(feature = "std")]
+impl_dbg!(u8, u16, u32, u64, u128);
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_hash_dbg {
 ($($t:ty),+) => {$(
     impl<const K: usize> Dbg<K, $t, IntKmer<K, $t>> for HashDbg<K, $t> {
         fn contains(&self, kmer: IntKmer<K, $t>) -> bool {
@@ -66,27 +145,26 @@ macro_rules! impl_traits {
     impl<const K: usize> DbgBuilder<K, $t, IntKmer<K, $t>, HashDbg<K, $t>> for HashDbgBuilder<K, $t> {
         fn new() -> Self {
             Self {
-                data: HashSet::new(),
+                data: AHashSet::with_hasher(ahash_state()),
             }
         }
 
-        fn insert(mut self, kmer: IntKmer<K, $t>) -> Self {
+        fn insert(&mut self, kmer: IntKmer<K, $t>) {
             self.data.insert(kmer.to_int());
-            self
         }
 
         fn build(self) -> HashDbg<K, $t> {
             self
         }
     }
+)*}}
 
-    impl<const K: usize> Dbg<K, $t, IntKmer<K, $t>> for DenseDbg<K, $t> {
-        fn contains(&self, kmer: IntKmer<K, $t>) -> bool {
-            let pos = kmer.to_int() as usize;
-            self.data.get_bit(pos).expect("Out of bounds")
-        }
-    }
+#[cfg(feature = "alloc")]
+impl_hash_dbg!(u8, u16, u32, u64, u128);
 
+#[cfg(feature = "std")]
+macro_rules! impl_dense_sparse_dbg_builder {
+($($t:ty),+) => {$(
     impl<const K: usize> DbgBuilder<K, $t, IntKmer<K, $t>, DenseDbg<K, $t>> for DenseDbgBuilder<K, $t> {
         fn new() -> Self {
             Self {
@@ -95,10 +173,9 @@ macro_rules! impl_traits {
             }
         }
 
-        fn insert(mut self, kmer: IntKmer<K, $t>) -> Self {
+        fn insert(&mut self, kmer: IntKmer<K, $t>) {
             let pos = kmer.to_int() as usize;
             self.data.set_bit(pos, true).expect("Out of bounds");
-            self
         }
 
         fn build(self) -> DenseDbg<K, $t> {
@@ -106,17 +183,6 @@ macro_rules! impl_traits {
         }
     }
 
-    impl<const K: usize> Dbg<K, $t, IntKmer<K, $t>> for SparseDbg<K, $t> {
-        fn contains(&self, kmer: IntKmer<K, $t>) -> bool {
-            let pos = kmer.to_int() as usize;
-            if let Some(rank) = self.data.rank(pos) {
-                self.data.select(rank) == Some(pos)
-            } else {
-                false
-            }
-        }
-    }
-
     impl<const K: usize> DbgBuilder<K, $t, IntKmer<K, $t>, SparseDbg<K, $t>> for SparseDbgBuilder<K, $t> {
         fn new() -> Self {
             Self {
@@ -124,9 +190,8 @@ macro_rules! impl_traits {
             }
         }
 
-        fn insert(mut self, kmer: IntKmer<K, $t>) -> Self {
+        fn insert(&mut self, kmer: IntKmer<K, $t>) {
             self.positions.insert(kmer.to_int());
-            self
         }
 
         fn build(self) -> SparseDbg<K, $t> {
@@ -141,8 +206,133 @@ macro_rules! impl_traits {
     }
 )*}}
 
-impl_traits!(u8, u16, u32, u64, u128);
+#[cfg(feature = "std")]
+impl_dense_sparse_dbg_builder!(u8, u16, u32, u64, u128);
 
+/// A [`DbgBuilder`] that can be driven from multiple worker threads, mirroring
+/// the blocking [`DbgBuilder::build`] with a [`build_parallel`](Self::build_parallel)
+/// that feeds on [`Fasta::parallel_process`].
+///
+/// Each worker accumulates into its own thread-local shard (a fresh `Self`),
+/// so there's no contention on a shared graph while reading; [`merge`](Self::merge)
+/// folds the shards into the final `Dbg` once every record has been seen.
+#[cfg(feature = "std")]
+pub trait ParallelDbgBuilder<const K: usize, T, KT, DT>: DbgBuilder<K, T, KT, DT> + Sized
+where
+    T: Base,
+    KT: Kmer<K, T>,
+    DT: Dbg<K, T, KT>,
+{
+    fn merge(shards: Vec<Self>) -> DT;
+
+    fn build_parallel<F>(reads: Fasta, threads: u32, queue_len: usize, f: F) -> DT
+    where
+        Self: Send + 'static,
+        F: Send + Sync + Fn(Iter<u8>, &mut Self),
+    {
+        // `thread_local!` can't hold a `Self`-typed static (its initializer is
+        // a separate item and can't see the enclosing generic parameter), so
+        // shards are instead keyed by `ThreadId` in a shared registry; each
+        // worker still only ever touches its own entry.
+        let shards: Mutex<HashMap<ThreadId, Arc<Mutex<Self>>>> = Mutex::new(HashMap::new());
+        reads.parallel_process(threads, queue_len, |bytes| {
+            let shard = shards
+                .lock()
+                .unwrap()
+                .entry(thread::current().id())
+                .or_insert_with(|| Arc::new(Mutex::new(Self::new())))
+                .clone();
+            f(bytes, &mut shard.lock().unwrap());
+        });
+
+        let shards = shards
+            .into_inner()
+            .unwrap()
+            .into_values()
+            .map(|shard| Arc::try_unwrap(shard).ok().unwrap().into_inner().unwrap())
+            .collect();
+        Self::merge(shards)
+    }
+}
+
+#[cfg(feature = "std")]
+macro_rules! impl_parallel_dbg_builder {
+($($t:ty),+) => {$(
+    impl<const K: usize> ParallelDbgBuilder<K, $t, IntKmer<K, $t>, HashDbg<K, $t>> for HashDbgBuilder<K, $t> {
+        fn merge(shards: Vec<Self>) -> HashDbg<K, $t> {
+            let mut shards = shards.into_iter();
+            let mut acc = shards.next().unwrap_or_else(Self::new);
+            for shard in shards {
+                acc.data.extend(shard.data);
+            }
+            acc.build()
+        }
+    }
+
+    impl<const K: usize> ParallelDbgBuilder<K, $t, IntKmer<K, $t>, DenseDbg<K, $t>> for DenseDbgBuilder<K, $t> {
+        fn merge(shards: Vec<Self>) -> DenseDbg<K, $t> {
+            let mut shards = shards.into_iter();
+            let mut acc = shards.next().unwrap_or_else(Self::new);
+            for shard in shards {
+                for pos in 0..(1usize << (2 * K)) {
+                    if shard.data.get_bit(pos).expect("Out of bounds") {
+                        acc.data.set_bit(pos, true).expect("Out of bounds");
+                    }
+                }
+            }
+            acc.build()
+        }
+    }
+
+    impl<const K: usize> ParallelDbgBuilder<K, $t, IntKmer<K, $t>, SparseDbg<K, $t>> for SparseDbgBuilder<K, $t> {
+        fn merge(shards: Vec<Self>) -> SparseDbg<K, $t> {
+            let mut acc = Self::new();
+            for shard in shards {
+                acc.positions.extend(shard.positions);
+            }
+            acc.build()
+        }
+    }
+)*}}
+
+#[cfg(feature = "std")]
+impl_parallel_dbg_builder!(u8, u16, u32, u64, u128);
+
+#[cfg(feature = "std")]
+impl<const K: usize, T: Base + core::hash::Hash> Serializable for HashDbg<K, T> {
+    fn serialize_into<W: Write>(&self, mut writer: W) -> Result<usize> {
+        let mut sorted: std::vec::Vec<T> = self.data.iter().copied().collect();
+        sorted.sort_unstable();
+        let width = core::mem::size_of::<T>();
+        let mut n = writer.write(&(sorted.len() as u64).to_le_bytes())?;
+        for value in sorted {
+            let bytes = value.to_u128().expect("value fits in u128").to_le_bytes();
+            n += writer.write(&bytes[..width])?;
+        }
+        Ok(n)
+    }
+
+    fn deserialize_from<R: Read>(mut reader: R) -> Result<Self> {
+        let mut len_bytes = [0u8; 8];
+        reader.read_exact(&mut len_bytes)?;
+        let len = u64::from_le_bytes(len_bytes) as usize;
+        let width = core::mem::size_of::<T>();
+        let mut data = AHashSet::with_capacity_and_hasher(len, ahash_state());
+        for _ in 0..len {
+            let mut full = [0u8; 16];
+            reader.read_exact(&mut full[..width])?;
+            let value = T::from(u128::from_le_bytes(full)).expect("value fits in T");
+            data.insert(value);
+        }
+        Ok(Self { data })
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        8 + self.data.len() * core::mem::size_of::<T>()
+    }
+}
+
+#[cfg(feature = "std")]
 impl<const K: usize, T: Base> Serializable for DenseDbg<K, T> {
     fn serialize_into<W: Write>(&self, mut writer: W) -> Result<usize> {
         self.data.serialize_into(&mut writer)
@@ -160,6 +350,7 @@ impl<const K: usize, T: Base> Serializable for DenseDbg<K, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<const K: usize, T: Base> Serializable for SparseDbg<K, T> {
     fn serialize_into<W: Write>(&self, mut writer: W) -> Result<usize> {
         self.data.serialize_into(&mut writer)
@@ -176,3 +367,49 @@ impl<const K: usize, T: Base> Serializable for SparseDbg<K, T> {
         self.data.size_in_bytes()
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::kmer::Canonical;
+
+    const K: usize = 4;
+
+    fn write_fasta(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, b">r1\nACGTACGTACGT\n>r2\nTTTTGGGGCCCC\n>r3\nAAAACCCCGGGG\n")
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn build_parallel_matches_build() {
+        let path = write_fasta("tinydbg_build_parallel_matches_build.fasta");
+
+        let sequential = {
+            let mut builder = HashDbgBuilder::<K, u64>::new();
+            Fasta::from_file(&path).process(|bytes| {
+                IntKmer::<K, u64>::iter_from_chars(bytes)
+                    .for_each(|kmer| builder.insert(kmer.canonical()));
+            });
+            builder.build()
+        };
+
+        let parallel = HashDbgBuilder::<K, u64>::build_parallel(
+            Fasta::from_file(&path),
+            2,
+            4,
+            |bytes, builder| {
+                IntKmer::<K, u64>::iter_from_chars(bytes)
+                    .for_each(|kmer| builder.insert(kmer.canonical()));
+            },
+        );
+
+        for value in 0u64..(1 << (2 * K)) {
+            let kmer = IntKmer::<K, u64>::from_int(value);
+            assert_eq!(sequential.contains(kmer), parallel.contains(kmer));
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
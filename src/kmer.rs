@@ -1,10 +1,47 @@
+#[cfg(feature = "std")]
+use anyhow::{bail, Result};
+use core::{iter::FilterMap, marker::PhantomData};
 use num_traits::int::PrimInt;
-use std::{iter::FilterMap, marker::PhantomData};
 
 pub trait Base: PrimInt {
     fn from_char(b: &u8) -> Option<Self>;
     fn to_char(self) -> u8;
     fn bases() -> [Self; 4];
+    /// Tag identifying this integer width in a serialized container header.
+    #[cfg(feature = "std")]
+    fn int_tag() -> IntTag;
+}
+
+/// Tag for the integer width backing a k-mer encoding, written into
+/// serialized containers so a loader can refuse a mismatched `T`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IntTag {
+    U8 = 0,
+    U16 = 1,
+    U32 = 2,
+    U64 = 3,
+    U128 = 4,
+}
+
+#[cfg(feature = "std")]
+impl IntTag {
+    const COUNT: u8 = 5;
+    const ALL: [Self; Self::COUNT as usize] =
+        [Self::U8, Self::U16, Self::U32, Self::U64, Self::U128];
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<u8> for IntTag {
+    type Error = anyhow::Error;
+
+    fn try_from(tag: u8) -> Result<Self> {
+        if tag >= Self::COUNT {
+            bail!("unknown integer type tag {tag}");
+        }
+        Ok(Self::ALL[tag as usize])
+    }
 }
 
 pub trait Kmer<const K: usize, T: Base>: Sized + Copy {
@@ -34,6 +71,7 @@ pub trait Kmer<const K: usize, T: Base>: Sized + Copy {
             _phantom: PhantomData,
         }
     }
+    #[allow(clippy::type_complexity)]
     fn iter_from_chars<'a, I: Iterator<Item = &'a u8>>(
         bytes: I,
     ) -> KmerIterator<K, T, FilterMap<I, fn(&u8) -> Option<T>>, Self> {
@@ -90,7 +128,7 @@ where
 pub struct IntKmer<const K: usize, T: Base>(T);
 
 macro_rules! impl_traits {
-($($t:ty),+) => {$(
+($($t:ty => $tag:ident),+) => {$(
     impl Base for $t {
         #[inline]
         fn from_char(b: &u8) -> Option<Self> {
@@ -111,6 +149,11 @@ macro_rules! impl_traits {
         fn bases() -> [Self; 4] {
             [0, 1, 2, 3]
         }
+        #[cfg(feature = "std")]
+        #[inline]
+        fn int_tag() -> IntTag {
+            IntTag::$tag
+        }
     }
 
     impl<const K: usize> Kmer<K, $t> for IntKmer<K, $t> {
@@ -150,7 +193,7 @@ macro_rules! impl_traits {
     }
 )*}}
 
-impl_traits!(u8, u16, u32, u64, u128);
+impl_traits!(u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128);
 
 impl<const K: usize> Canonical<K, u8> for IntKmer<K, u8> {
     fn rev_comp(self) -> Self {
@@ -193,7 +236,7 @@ impl<const K: usize> Canonical<K, u128> for IntKmer<K, u128> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -257,4 +300,14 @@ mod tests {
             assert_eq!(kmer.rev_comp().rev_comp().to_int(), i);
         }
     }
+    #[test]
+    fn int_tag_round_trip() {
+        for tag in IntTag::ALL {
+            assert_eq!(IntTag::try_from(tag as u8).unwrap(), tag);
+        }
+    }
+    #[test]
+    fn int_tag_rejects_unknown() {
+        assert!(IntTag::try_from(IntTag::COUNT).is_err());
+    }
 }
@@ -0,0 +1,222 @@
+use crate::container::{Repr, FORMAT_VERSION, HEADER_LEN, MAGIC};
+use crate::dbg::Dbg;
+use crate::kmer::{Base, IntKmer, IntTag, Kmer};
+use anyhow::{ensure, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::marker::PhantomData;
+use std::path::Path;
+use sucds::mii_sequences::EliasFano;
+use sucds::Serializable;
+
+fn parse_header(bytes: &[u8]) -> Result<(Repr, u64, IntTag)> {
+    ensure!(bytes.len() >= HEADER_LEN, "container truncated before header");
+    ensure!(bytes[..4] == MAGIC, "not a tinydbg container (bad magic)");
+    ensure!(
+        bytes[4] == FORMAT_VERSION,
+        "unsupported container format version {} (expected {FORMAT_VERSION})",
+        bytes[4]
+    );
+    let repr = Repr::try_from(bytes[5])?;
+    let k = u64::from_le_bytes(bytes[6..14].try_into().unwrap());
+    let tag = IntTag::try_from(bytes[14])?;
+    Ok((repr, k, tag))
+}
+
+/// A `DenseDbg` queried directly against a memory-mapped file instead of an
+/// owned bit vector, so many independent queries through one handle share
+/// one mapping with constant startup cost and low resident memory. Each call
+/// to `open` creates a new mapping, which the returned handle owns.
+///
+/// The mapping outlives every query, so `contains` reads straight from the
+/// OS page cache instead of deserializing the whole bit vector up front.
+pub struct MappedDenseDbg<const K: usize, T: Base> {
+    mmap: Mmap,
+    bits_offset: usize,
+    phantom: PhantomData<T>,
+}
+
+impl<const K: usize, T: Base> MappedDenseDbg<K, T> {
+    /// Memory-maps `path` and validates its header against `K`/`T`/`Repr::Dense`
+    /// without reading the bit vector itself.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the mapping is read-only for the handle's lifetime; callers
+        // are responsible for not mutating the file out from under it.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let (repr, k, tag) = parse_header(&mmap)?;
+        ensure!(
+            repr == Repr::Dense,
+            "expected a Dense representation, found {:?}",
+            repr
+        );
+        ensure!(k == K as u64, "expected K = {K}, found K = {k}");
+        ensure!(
+            tag == T::int_tag(),
+            "expected integer type {:?}, found {:?}",
+            T::int_tag(),
+            tag
+        );
+        // `BitVector::serialize_into` writes its packed `usize` words prefixed
+        // by an 8-byte word count, followed by an 8-byte bit length (unused
+        // here, since `K` already tells us how many bits to expect); skip the
+        // word count to reach the words themselves.
+        let bits_offset = HEADER_LEN + 8;
+        let body_len = (1usize << (2 * K)).div_ceil(64) * 8;
+        ensure!(
+            mmap.len() >= bits_offset + body_len,
+            "container truncated: expected {body_len} bytes of bit-vector body at offset {bits_offset}, found {}",
+            mmap.len().saturating_sub(bits_offset)
+        );
+        Ok(Self {
+            mmap,
+            bits_offset,
+            phantom: PhantomData,
+        })
+    }
+
+    #[inline]
+    fn get_bit(&self, pos: usize) -> bool {
+        let word_offset = self.bits_offset + (pos / 64) * 8;
+        let word = u64::from_le_bytes(
+            self.mmap[word_offset..word_offset + 8]
+                .try_into()
+                .expect("word is in bounds"),
+        );
+        (word >> (pos % 64)) & 1 == 1
+    }
+}
+
+impl<const K: usize, T: Base> Dbg<K, T, IntKmer<K, T>> for MappedDenseDbg<K, T>
+where
+    IntKmer<K, T>: Kmer<K, T>,
+{
+    fn contains(&self, kmer: IntKmer<K, T>) -> bool {
+        let pos = kmer.to_int().to_usize().expect("position fits in usize");
+        self.get_bit(pos)
+    }
+}
+
+/// A `SparseDbg` backed by a memory-mapped file.
+///
+/// Unlike [`MappedDenseDbg`]'s flat bit vector, Elias-Fano's encoding (high
+/// and low arrays plus a rank/select directory) isn't safely addressable as
+/// a borrowed view without `sucds` exposing one, so the structure is decoded
+/// once from the mapping on `open` and served from there; the mapping is
+/// still what lets the OS page cache answer repeated `open` calls without
+/// rereading the file from disk.
+pub struct MappedSparseDbg<const K: usize, T: Base> {
+    _mmap: Mmap,
+    data: EliasFano,
+    phantom: PhantomData<T>,
+}
+
+impl<const K: usize, T: Base> MappedSparseDbg<K, T> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let (repr, k, tag) = parse_header(&mmap)?;
+        ensure!(
+            repr == Repr::Sparse,
+            "expected a Sparse representation, found {:?}",
+            repr
+        );
+        ensure!(k == K as u64, "expected K = {K}, found K = {k}");
+        ensure!(
+            tag == T::int_tag(),
+            "expected integer type {:?}, found {:?}",
+            T::int_tag(),
+            tag
+        );
+        let data = EliasFano::deserialize_from(&mmap[HEADER_LEN..])?;
+        Ok(Self {
+            _mmap: mmap,
+            data,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<const K: usize, T: Base> Dbg<K, T, IntKmer<K, T>> for MappedSparseDbg<K, T>
+where
+    IntKmer<K, T>: Kmer<K, T>,
+{
+    fn contains(&self, kmer: IntKmer<K, T>) -> bool {
+        let pos = kmer.to_int().to_usize().expect("position fits in usize");
+        match self.data.rank(pos) {
+            Some(rank) => self.data.select(rank) == Some(pos),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::save;
+    use crate::dbg::{DbgBuilder, DenseDbgBuilder, SparseDbgBuilder};
+
+    const K: usize = 4;
+
+    fn some_kmers() -> [IntKmer<K, u32>; 3] {
+        [
+            IntKmer::from_chars(b"ATCG"),
+            IntKmer::from_chars(b"GGGG"),
+            IntKmer::from_chars(b"TTTT"),
+        ]
+    }
+
+    #[test]
+    fn mapped_dense_dbg_matches_owned() {
+        let mut builder = DenseDbgBuilder::<K, u32>::new();
+        some_kmers().iter().for_each(|&kmer| builder.insert(kmer));
+        let owned = builder.build();
+
+        let path = std::env::temp_dir().join("tinydbg_mapped_dense_dbg_matches_owned.bin");
+        save::<K, u32, _>(&owned, std::fs::File::create(&path).unwrap()).unwrap();
+        let mapped = MappedDenseDbg::<K, u32>::open(&path).unwrap();
+
+        for value in 0u32..(1 << (2 * K)) {
+            let kmer = IntKmer::<K, u32>::from_int(value);
+            assert_eq!(owned.contains(kmer), mapped.contains(kmer));
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn mapped_sparse_dbg_matches_owned() {
+        let mut builder = SparseDbgBuilder::<K, u32>::new();
+        some_kmers().iter().for_each(|&kmer| builder.insert(kmer));
+        let owned = builder.build();
+
+        let path = std::env::temp_dir().join("tinydbg_mapped_sparse_dbg_matches_owned.bin");
+        save::<K, u32, _>(&owned, std::fs::File::create(&path).unwrap()).unwrap();
+        let mapped = MappedSparseDbg::<K, u32>::open(&path).unwrap();
+
+        for value in 0u32..(1 << (2 * K)) {
+            let kmer = IntKmer::<K, u32>::from_int(value);
+            assert_eq!(owned.contains(kmer), mapped.contains(kmer));
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn mapped_dense_dbg_rejects_truncated_body() {
+        let mut builder = DenseDbgBuilder::<K, u32>::new();
+        some_kmers().iter().for_each(|&kmer| builder.insert(kmer));
+        let owned = builder.build();
+
+        let path = std::env::temp_dir().join("tinydbg_mapped_dense_dbg_rejects_truncated_body.bin");
+        save::<K, u32, _>(&owned, std::fs::File::create(&path).unwrap()).unwrap();
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(HEADER_LEN as u64 + 8 + 4).unwrap();
+        drop(file);
+
+        let err = MappedDenseDbg::<K, u32>::open(&path).err().unwrap();
+        assert!(err.to_string().contains("truncated"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
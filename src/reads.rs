@@ -1,6 +1,5 @@
-use seq_io::fasta::{Reader, RefRecord};
-use seq_io::parallel::read_process_fasta_records;
-use seq_io::BaseRecord;
+use seq_io::fasta::{Reader, Record, RefRecord};
+use seq_io::parallel::parallel_fasta;
 use std::fs::File;
 use std::path::Path;
 use std::slice::Iter;
@@ -31,7 +30,7 @@ impl ReadProcess for Fasta {
     }
 
     fn parallel_process<F: Send + Sync + Fn(Iter<u8>)>(self, threads: u32, queue_len: usize, f: F) {
-        read_process_fasta_records(
+        parallel_fasta(
             self.reader,
             threads,
             queue_len,